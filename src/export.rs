@@ -0,0 +1,221 @@
+//! Plain-text export of a sheet (with changeset edits applied) for diffing
+//! or embedding in docs. Reuses the same `CellValue`/`Data` match arms as
+//! the save path, just routed to a text `Writer` instead of `rust_xlsxwriter`.
+
+use crate::workbook::{CellValue, Changeset};
+use crate::xlsx_meta::XlsxMeta;
+use anyhow::{Context, Result};
+use calamine::{Data, Range, Reader, open_workbook_auto};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+/// Export `sheet_name`, with `changeset` edits applied, as CSV.
+pub fn export_sheet_csv(
+    original_path: &Path,
+    sheet_name: &str,
+    changeset: &Changeset,
+    output_path: &Path,
+) -> Result<()> {
+    let (range, edits_index) = load_sheet(original_path, sheet_name, changeset)?;
+    let mut out = File::create(output_path)
+        .with_context(|| format!("Failed to create {}", output_path.display()))?;
+
+    for (row_idx, row) in range.rows().enumerate() {
+        let fields: Vec<String> = row
+            .iter()
+            .enumerate()
+            .map(|(col_idx, cell)| csv_escape(&cell_text(&edits_index, row_idx, col_idx, cell)))
+            .collect();
+        writeln!(out, "{}", fields.join(","))?;
+    }
+
+    Ok(())
+}
+
+/// Export `sheet_name`, with `changeset` edits applied, as an AsciiDoc
+/// table. Column weights come from the source column widths, when known.
+pub fn export_sheet_asciidoc(
+    original_path: &Path,
+    sheet_name: &str,
+    changeset: &Changeset,
+    output_path: &Path,
+) -> Result<()> {
+    let (range, edits_index) = load_sheet(original_path, sheet_name, changeset)?;
+    // `<col min max>` widths are absolute sheet columns, but `range.width()`
+    // and the cell loop below are indexed relative to the range's start.
+    let (_, start_col) = range.start().unwrap_or((0, 0));
+    let widths = XlsxMeta::open(original_path)?
+        .and_then(|mut meta| meta.column_widths(sheet_name, start_col as usize).ok())
+        .unwrap_or_default();
+
+    let num_cols = range.width();
+    let cols = (0..num_cols)
+        .map(|col_idx| {
+            widths
+                .get(&col_idx)
+                .map(|w| w.round().max(1.0) as u64)
+                .unwrap_or(1)
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+
+    let mut out = File::create(output_path)
+        .with_context(|| format!("Failed to create {}", output_path.display()))?;
+
+    writeln!(out, "[cols=\"{cols}\"]")?;
+    writeln!(out, "|===")?;
+    for (row_idx, row) in range.rows().enumerate() {
+        writeln!(out)?;
+        let cells: Vec<String> = row
+            .iter()
+            .enumerate()
+            .map(|(col_idx, cell)| {
+                format!("|{}", asciidoc_escape(&cell_text(&edits_index, row_idx, col_idx, cell)))
+            })
+            .collect();
+        writeln!(out, "{}", cells.join(" "))?;
+    }
+    writeln!(out, "|===")?;
+
+    Ok(())
+}
+
+fn load_sheet<'a>(
+    original_path: &Path,
+    sheet_name: &str,
+    changeset: &'a Changeset,
+) -> Result<(Range<Data>, HashMap<(usize, usize), &'a crate::workbook::Edit>)> {
+    let mut workbook = open_workbook_auto(original_path)
+        .with_context(|| format!("Failed to open workbook for reading: {}", original_path.display()))?;
+    let range = workbook
+        .worksheet_range(sheet_name)
+        .with_context(|| format!("Failed to read sheet: {}", sheet_name))?;
+    let edits_index = changeset.edits_index_for_sheet(sheet_name);
+    Ok((range, edits_index))
+}
+
+fn cell_text(
+    edits_index: &HashMap<(usize, usize), &crate::workbook::Edit>,
+    row_idx: usize,
+    col_idx: usize,
+    cell: &Data,
+) -> String {
+    match edits_index.get(&(row_idx, col_idx)) {
+        Some(edit) => cell_value_text(&edit.new_value),
+        None => calamine_data_text(cell),
+    }
+}
+
+fn cell_value_text(value: &CellValue) -> String {
+    match value {
+        CellValue::Empty => String::new(),
+        CellValue::String(s) => s.clone(),
+        CellValue::Int(n) => n.to_string(),
+        CellValue::Float(n) => n.to_string(),
+        CellValue::Bool(b) => b.to_string(),
+        CellValue::Error(e) => e.clone(),
+        CellValue::DateTime(serial) => excel_serial_to_iso(*serial),
+        CellValue::Hyperlink { url, text } => text.clone().unwrap_or_else(|| url.clone()),
+    }
+}
+
+fn calamine_data_text(data: &Data) -> String {
+    match data {
+        Data::Empty => String::new(),
+        Data::String(s) => s.clone(),
+        Data::Int(n) => n.to_string(),
+        Data::Float(n) => n.to_string(),
+        Data::Bool(b) => b.to_string(),
+        Data::Error(e) => format!("{:?}", e),
+        Data::DateTime(dt) => excel_serial_to_iso(dt.as_f64()),
+        Data::DateTimeIso(s) => s.clone(),
+        Data::DurationIso(s) => s.clone(),
+    }
+}
+
+/// Render an Excel 1900-date-system serial as an ISO 8601 string, dropping
+/// the time-of-day component when the serial is a whole number of days.
+fn excel_serial_to_iso(serial: f64) -> String {
+    const EXCEL_TO_UNIX_EPOCH_DAYS: i64 = 25569;
+
+    let days_since_unix_epoch = serial.floor() as i64 - EXCEL_TO_UNIX_EPOCH_DAYS;
+    let (year, month, day) = civil_from_days(days_since_unix_epoch);
+
+    let fraction = serial.fract();
+    if fraction.abs() < 1e-9 {
+        return format!("{year:04}-{month:02}-{day:02}");
+    }
+
+    let total_seconds = (fraction * 86_400.0).round() as i64;
+    let hour = total_seconds / 3600;
+    let minute = (total_seconds % 3600) / 60;
+    let second = total_seconds % 60;
+    format!("{year:04}-{month:02}-{day:02}T{hour:02}:{minute:02}:{second:02}")
+}
+
+/// Howard Hinnant's `civil_from_days`: days since 1970-01-01 -> (year, month, day).
+fn civil_from_days(z: i64) -> (i64, u32, u32) {
+    let z = z + 719_468;
+    let era = if z >= 0 { z } else { z - 146_096 } / 146_097;
+    let doe = (z - era * 146_097) as u64;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let y = yoe as i64 + era * 400;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let y = if m <= 2 { y + 1 } else { y };
+    (y, m, d)
+}
+
+fn csv_escape(field: &str) -> String {
+    if field.contains(',') || field.contains('"') || field.contains('\n') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Escape a cell's text for embedding in an AsciiDoc table cell: a literal
+/// `|` would otherwise be read as the next cell's delimiter, and a newline
+/// would otherwise be read as a new table row.
+fn asciidoc_escape(field: &str) -> String {
+    field.replace('|', "\\|").replace('\n', " +\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn whole_day_serial_has_no_time_component() {
+        assert_eq!(excel_serial_to_iso(45_000.0), "2023-03-15");
+    }
+
+    #[test]
+    fn fractional_serial_includes_time_of_day() {
+        assert_eq!(excel_serial_to_iso(45_000.5), "2023-03-15T12:00:00");
+    }
+
+    #[test]
+    fn civil_from_days_round_trips_the_unix_epoch() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+    }
+
+    #[test]
+    fn csv_escape_quotes_fields_with_special_characters() {
+        assert_eq!(csv_escape("plain"), "plain");
+        assert_eq!(csv_escape("a,b"), "\"a,b\"");
+        assert_eq!(csv_escape("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn asciidoc_escape_protects_cell_delimiters() {
+        assert_eq!(asciidoc_escape("plain"), "plain");
+        assert_eq!(asciidoc_escape("Before|After"), "Before\\|After");
+        assert_eq!(asciidoc_escape("line one\nline two"), "line one +\nline two");
+    }
+}
@@ -0,0 +1,140 @@
+//! Resolves edits addressed by header-row column name instead of raw
+//! `(row, col)` indices, so callers don't break when the header isn't on
+//! the first row or when columns get reordered.
+
+use crate::workbook::CellCoordinate;
+use anyhow::{Result, anyhow, bail};
+use calamine::{Cell, Data, Range};
+use std::collections::HashMap;
+
+/// A located header row, mapping each of its column labels to a column index.
+pub struct HeaderIndex {
+    sheet_name: String,
+    header_row: usize,
+    columns: HashMap<String, usize>,
+}
+
+impl HeaderIndex {
+    /// Scan `range` for the first row in which every label in
+    /// `expected_headers` appears as a cell, recording every label found on
+    /// that row (not just the expected ones).
+    pub fn locate(sheet_name: &str, range: &Range<Data>, expected_headers: &[&str]) -> Result<Self> {
+        for (row_idx, row) in range.rows().enumerate() {
+            let mut columns = HashMap::new();
+            for (col_idx, cell) in row.iter().enumerate() {
+                if let Data::String(label) = cell {
+                    columns.insert(label.clone(), col_idx);
+                }
+            }
+            if expected_headers.iter().all(|h| columns.contains_key(*h)) {
+                return Ok(Self {
+                    sheet_name: sheet_name.to_string(),
+                    header_row: row_idx,
+                    columns,
+                });
+            }
+        }
+        bail!(
+            "Could not locate a header row containing all of {:?} in sheet \"{}\"",
+            expected_headers,
+            sheet_name
+        );
+    }
+
+    /// Resolve a `(data_row, column_name)` pair into a concrete coordinate.
+    /// `data_row` is 0-indexed relative to the first row below the header.
+    pub fn resolve(&self, data_row: usize, column_name: &str) -> Result<CellCoordinate> {
+        let col = self
+            .columns
+            .get(column_name)
+            .ok_or_else(|| anyhow!("Unknown header column \"{column_name}\" in sheet \"{}\"", self.sheet_name))?;
+        Ok(CellCoordinate {
+            sheet_name: self.sheet_name.clone(),
+            row: self.header_row + 1 + data_row,
+            col: *col,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range_from_rows(rows: Vec<Vec<Data>>) -> Range<Data> {
+        Range::from_sparse(
+            rows.into_iter()
+                .enumerate()
+                .flat_map(|(row_idx, row)| {
+                    row.into_iter()
+                        .enumerate()
+                        .map(move |(col_idx, cell)| Cell::new((row_idx as u32, col_idx as u32), cell))
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn locates_header_row_and_resolves_multi_letter_columns() {
+        let mut row = vec![Data::Empty; 28];
+        row[0] = Data::String("Name".to_string());
+        row[27] = Data::String("Notes".to_string());
+        let range = range_from_rows(vec![row, vec![Data::String("Ada".to_string())]]);
+
+        let index = HeaderIndex::locate("Sheet1", &range, &["Name", "Notes"]).unwrap();
+        assert_eq!(
+            index.resolve(0, "Name").unwrap(),
+            CellCoordinate {
+                sheet_name: "Sheet1".to_string(),
+                row: 1,
+                col: 0,
+            }
+        );
+        assert_eq!(
+            index.resolve(0, "Notes").unwrap(),
+            CellCoordinate {
+                sheet_name: "Sheet1".to_string(),
+                row: 1,
+                col: 27,
+            }
+        );
+    }
+
+    #[test]
+    fn locate_fails_when_no_row_has_every_expected_header() {
+        let range = range_from_rows(vec![vec![Data::String("Name".to_string())]]);
+        assert!(HeaderIndex::locate("Sheet1", &range, &["Name", "Email"]).is_err());
+    }
+
+    #[test]
+    fn locate_skips_past_rows_missing_a_header_to_find_the_real_one() {
+        let range = range_from_rows(vec![
+            vec![Data::String("Report".to_string())],
+            vec![
+                Data::String("Name".to_string()),
+                Data::String("Email".to_string()),
+            ],
+        ]);
+        let index = HeaderIndex::locate("Sheet1", &range, &["Name", "Email"]).unwrap();
+        assert_eq!(
+            index.resolve(0, "Email").unwrap().row,
+            2 // header is on row 1 (0-indexed), so the first data row is row 2
+        );
+    }
+
+    #[test]
+    fn duplicate_header_labels_keep_the_last_occurrence() {
+        let range = range_from_rows(vec![vec![
+            Data::String("Name".to_string()),
+            Data::String("Name".to_string()),
+        ]]);
+        let index = HeaderIndex::locate("Sheet1", &range, &["Name"]).unwrap();
+        assert_eq!(index.resolve(0, "Name").unwrap().col, 1);
+    }
+
+    #[test]
+    fn resolve_fails_for_unknown_column_name() {
+        let range = range_from_rows(vec![vec![Data::String("Name".to_string())]]);
+        let index = HeaderIndex::locate("Sheet1", &range, &["Name"]).unwrap();
+        assert!(index.resolve(0, "Email").is_err());
+    }
+}
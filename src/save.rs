@@ -1,10 +1,14 @@
-use crate::workbook::{CellCoordinate, CellValue, Changeset};
+use crate::workbook::{CellValue, Changeset, ValidationEdit, ValidationRule};
+use crate::xlsx_meta::{CellFormat, CellHyperlink, SheetFormats, XlsxMeta};
 use anyhow::{Context, Result};
 use calamine::{Data, Reader, open_workbook_auto};
-use rust_xlsxwriter::{Format, Workbook as XlsxWorkbook, Worksheet};
+use rust_xlsxwriter::{DataValidation, Format, Workbook as XlsxWorkbook, Worksheet};
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
+const DEFAULT_DATETIME_FORMAT: &str = "yyyy-mm-dd hh:mm:ss";
+
 /// Save the workbook with changes applied from the changeset.
 /// Creates a backup of the original file before overwriting.
 pub fn save_workbook_with_changes(
@@ -21,6 +25,11 @@ pub fn save_workbook_with_changes(
     // Get all sheet names
     let sheet_names = workbook.sheet_names();
 
+    // Parse the workbook-level xlsx parts (styles, workbook.xml/rels) once
+    // up front, rather than having each sheet below re-open the zip archive
+    // and re-parse styles.xml for itself.
+    let mut xlsx_meta = XlsxMeta::open(original_path)?;
+
     // Copy each sheet to the new workbook, applying edits
     for sheet_name in sheet_names {
         // Get the range for this sheet
@@ -28,37 +37,83 @@ pub fn save_workbook_with_changes(
             .worksheet_range(&sheet_name)
             .with_context(|| format!("Failed to read sheet: {}", sheet_name))?;
 
+        // Get the formula range for this sheet, if any. Shared formulas are
+        // already expanded per-cell by calamine, so a formula cell here is
+        // always ready to write as-is. Not every format exposes formulas, so
+        // treat a read failure the same as "no formulas".
+        let formulas = workbook
+            .worksheet_formula(&sheet_name)
+            .unwrap_or_default();
+
+        // calamine indexes `range.rows()` relative to the range's start, not
+        // the sheet's absolute position, so the lookups below need the same
+        // offset applied to line up with the absolute coordinates the xlsx
+        // metadata below is read from.
+        let (start_row, start_col) = range.start().unwrap_or((0, 0));
+        let range_start = (start_row as usize, start_col as usize);
+
+        // Source number formats for this sheet, so non-edited cells keep
+        // their original currency/percentage/date formatting.
+        let formats: SheetFormats = xlsx_meta
+            .as_mut()
+            .and_then(|meta| meta.sheet_formats(&sheet_name, range_start).ok())
+            .unwrap_or_default();
+
+        // Source hyperlinks for this sheet, so non-edited cells stay links
+        // instead of coming back as bare strings.
+        let hyperlinks: HashMap<(usize, usize), CellHyperlink> = xlsx_meta
+            .as_mut()
+            .and_then(|meta| meta.sheet_hyperlinks(&sheet_name, range_start).ok())
+            .unwrap_or_default();
+
         // Create a new worksheet
         let worksheet = output_workbook.add_worksheet();
         worksheet.set_name(&sheet_name)
             .with_context(|| format!("Failed to set worksheet name: {}", sheet_name))?;
 
-        // Get edits for this sheet
-        let edits_for_sheet = changeset.edits_for_sheet(&sheet_name);
+        // Get edits for this sheet, indexed by coordinate so the per-cell
+        // check below is a single hash probe instead of a linear scan.
+        let edits_index = changeset.edits_index_for_sheet(&sheet_name);
 
         // Copy cells from the original sheet, applying edits
         for (row_idx, row) in range.rows().enumerate() {
             for (col_idx, cell) in row.iter().enumerate() {
-                // Check if this cell has an edit
-                let coord = CellCoordinate {
-                    sheet_name: sheet_name.clone(),
-                    row: row_idx,
-                    col: col_idx,
-                };
-
-                let has_edit = edits_for_sheet.iter().any(|(c, _)| **c == coord);
-
-                if has_edit {
-                    // Write the edited value
-                    if let Some((_, edit)) = edits_for_sheet.iter().find(|(c, _)| **c == coord) {
-                        write_cell_value(worksheet, row_idx as u32, col_idx as u16, &edit.new_value)?;
-                    }
+                if let Some(edit) = edits_index.get(&(row_idx, col_idx)) {
+                    // Write the edited value, overriding any source formula
+                    write_cell_value(worksheet, row_idx as u32, col_idx as u16, &edit.new_value)?;
+                } else if let Some(formula) = formulas
+                    // The formula range has its own start, so probe it by
+                    // absolute position rather than by these relative indices.
+                    .get_value((start_row + row_idx as u32, start_col + col_idx as u32))
+                    .filter(|f| !f.is_empty())
+                {
+                    // Keep the cell live instead of freezing it to its cached
+                    // value, but still apply the source format (e.g. a SUM
+                    // formatted as currency) the same way a plain value would.
+                    let format = formats.get(&(row_idx, col_idx));
+                    write_formula_preserving_format(
+                        worksheet,
+                        row_idx as u32,
+                        col_idx as u16,
+                        formula.as_str(),
+                        format,
+                    )?;
+                } else if let Some(link) = hyperlinks.get(&(row_idx, col_idx)) {
+                    // Keep the cell a clickable link instead of a bare string
+                    write_hyperlink(worksheet, row_idx as u32, col_idx as u16, link, cell)?;
                 } else {
-                    // Write the original value
-                    write_calamine_data(worksheet, row_idx as u32, col_idx as u16, cell)?;
+                    // Write the original value, preserving its source format
+                    let format = formats.get(&(row_idx, col_idx));
+                    write_calamine_data(worksheet, row_idx as u32, col_idx as u16, cell, format)?;
                 }
             }
         }
+
+        // Attach data-validation rules last, since they apply to whole
+        // ranges rather than individual cells.
+        for validation in changeset.validations_for_sheet(&sheet_name) {
+            write_validation(worksheet, validation)?;
+        }
     }
 
     // Create backup of original file
@@ -116,16 +171,61 @@ fn write_cell_value(
             let format = Format::new().set_num_format("yyyy-mm-dd hh:mm:ss");
             worksheet.write_number_with_format(row, col, *serial, &format)?;
         }
+        CellValue::Hyperlink { url, text } => match text {
+            Some(text) => {
+                worksheet.write_url_with_text(row, col, url.as_str(), text)?;
+            }
+            None => {
+                worksheet.write_url(row, col, url.as_str())?;
+            }
+        },
     }
     Ok(())
 }
 
-/// Write calamine Data to a worksheet cell
+/// Re-emit a source hyperlink, using the cell's existing text as the label.
+fn write_hyperlink(
+    worksheet: &mut Worksheet,
+    row: u32,
+    col: u16,
+    link: &CellHyperlink,
+    cell: &Data,
+) -> Result<()> {
+    match cell {
+        Data::String(text) if !text.is_empty() => {
+            worksheet.write_url_with_text(row, col, link.url.as_str(), text)?;
+        }
+        _ => {
+            worksheet.write_url(row, col, link.url.as_str())?;
+        }
+    }
+    Ok(())
+}
+
+/// Attach one validation edit's rule to its target range.
+fn write_validation(worksheet: &mut Worksheet, validation: &ValidationEdit) -> Result<()> {
+    let data_validation = match &validation.rule {
+        ValidationRule::List(items) => DataValidation::new().allow_list_strings(items)?,
+    };
+    let range = &validation.range;
+    worksheet.add_data_validation(
+        range.first_row as u32,
+        range.first_col as u16,
+        range.last_row as u32,
+        range.last_col as u16,
+        &data_validation,
+    )?;
+    Ok(())
+}
+
+/// Write calamine Data to a worksheet cell, applying `format` (the source
+/// cell's number format, if one was found) rather than a hardcoded default.
 fn write_calamine_data(
     worksheet: &mut Worksheet,
     row: u32,
     col: u16,
     data: &Data,
+    format: Option<&CellFormat>,
 ) -> Result<()> {
     match data {
         Data::Empty => {
@@ -135,10 +235,10 @@ fn write_calamine_data(
             worksheet.write_string(row, col, s)?;
         }
         Data::Int(n) => {
-            worksheet.write_number(row, col, *n as f64)?;
+            write_number_preserving_format(worksheet, row, col, *n as f64, format)?;
         }
         Data::Float(n) => {
-            worksheet.write_number(row, col, *n)?;
+            write_number_preserving_format(worksheet, row, col, *n, format)?;
         }
         Data::Bool(b) => {
             worksheet.write_boolean(row, col, *b)?;
@@ -148,9 +248,11 @@ fn write_calamine_data(
             worksheet.write_string(row, col, &format!("{:?}", e))?;
         }
         Data::DateTime(dt) => {
-            // Write as Excel date/time
-            let format = Format::new().set_num_format("yyyy-mm-dd hh:mm:ss");
-            worksheet.write_number_with_format(row, col, dt.as_f64(), &format)?;
+            // Write as Excel date/time, reusing the source format code when
+            // one was found instead of collapsing every serial to one shape.
+            let num_format = format.map(|f| f.code.as_str()).unwrap_or(DEFAULT_DATETIME_FORMAT);
+            let xlsx_format = Format::new().set_num_format(num_format);
+            worksheet.write_number_with_format(row, col, dt.as_f64(), &xlsx_format)?;
         }
         Data::DateTimeIso(s) => {
             // Write ISO date string
@@ -163,3 +265,46 @@ fn write_calamine_data(
     }
     Ok(())
 }
+
+/// Write a formula, applying the source cell's format (currency, percentage,
+/// date/time serial, etc.) when one is known, the same way a plain value
+/// carrying that format would be written.
+fn write_formula_preserving_format(
+    worksheet: &mut Worksheet,
+    row: u32,
+    col: u16,
+    formula: &str,
+    format: Option<&CellFormat>,
+) -> Result<()> {
+    match format {
+        Some(format) => {
+            let xlsx_format = Format::new().set_num_format(&format.code);
+            worksheet.write_formula_with_format(row, col, formula, &xlsx_format)?;
+        }
+        None => {
+            worksheet.write_formula(row, col, formula)?;
+        }
+    }
+    Ok(())
+}
+
+/// Write a plain number, applying the source format (currency, percentage,
+/// date/time serial, etc.) when one is known.
+fn write_number_preserving_format(
+    worksheet: &mut Worksheet,
+    row: u32,
+    col: u16,
+    value: f64,
+    format: Option<&CellFormat>,
+) -> Result<()> {
+    match format {
+        Some(format) => {
+            let xlsx_format = Format::new().set_num_format(&format.code);
+            worksheet.write_number_with_format(row, col, value, &xlsx_format)?;
+        }
+        None => {
+            worksheet.write_number(row, col, value)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+
+/// Coordinates identifying a single cell within a workbook.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CellCoordinate {
+    pub sheet_name: String,
+    pub row: usize,
+    pub col: usize,
+}
+
+/// A value to write into a cell, independent of the source file format.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CellValue {
+    Empty,
+    String(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Error(String),
+    DateTime(f64),
+    /// A clickable link. `text` is the displayed label; when absent, the
+    /// URL itself is shown.
+    Hyperlink { url: String, text: Option<String> },
+}
+
+/// A rectangular cell range, used to anchor a data-validation rule.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CellRange {
+    pub sheet_name: String,
+    pub first_row: usize,
+    pub first_col: usize,
+    pub last_row: usize,
+    pub last_col: usize,
+}
+
+impl CellRange {
+    /// A range covering a single cell.
+    pub fn single(coord: CellCoordinate) -> Self {
+        Self {
+            sheet_name: coord.sheet_name,
+            first_row: coord.row,
+            first_col: coord.col,
+            last_row: coord.row,
+            last_col: coord.col,
+        }
+    }
+}
+
+/// A data-validation rule to attach to a range when saving.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationRule {
+    /// Restrict entry to one of a fixed list of strings (a dropdown).
+    List(Vec<String>),
+}
+
+/// A validation rule pending attachment to a range.
+#[derive(Debug, Clone)]
+pub struct ValidationEdit {
+    pub range: CellRange,
+    pub rule: ValidationRule,
+}
+
+/// A single requested change to one cell.
+#[derive(Debug, Clone)]
+pub struct Edit {
+    pub new_value: CellValue,
+}
+
+/// A set of pending edits to apply when a workbook is saved.
+#[derive(Debug, Clone, Default)]
+pub struct Changeset {
+    edits: Vec<(CellCoordinate, Edit)>,
+    validations: Vec<ValidationEdit>,
+}
+
+impl Changeset {
+    pub fn new() -> Self {
+        Self {
+            edits: Vec::new(),
+            validations: Vec::new(),
+        }
+    }
+
+    /// Record an edit, overwriting any previous edit at the same coordinate.
+    pub fn set(&mut self, coord: CellCoordinate, new_value: CellValue) {
+        self.edits.retain(|(c, _)| *c != coord);
+        self.edits.push((coord, Edit { new_value }));
+    }
+
+    /// All edits targeting the given sheet, indexed by `(row, col)` for O(1)
+    /// lookup, e.g. while copying a whole sheet.
+    pub fn edits_index_for_sheet(&self, sheet_name: &str) -> HashMap<(usize, usize), &Edit> {
+        self.edits
+            .iter()
+            .filter(|(c, _)| c.sheet_name == sheet_name)
+            .map(|(c, e)| ((c.row, c.col), e))
+            .collect()
+    }
+
+    /// Attach a data-validation rule to `range`.
+    pub fn add_validation(&mut self, range: CellRange, rule: ValidationRule) {
+        self.validations.push(ValidationEdit { range, rule });
+    }
+
+    /// All validation rules targeting the given sheet, in no particular order.
+    pub fn validations_for_sheet(&self, sheet_name: &str) -> Vec<&ValidationEdit> {
+        self.validations
+            .iter()
+            .filter(|v| v.range.sheet_name == sheet_name)
+            .collect()
+    }
+}
@@ -0,0 +1,598 @@
+//! Extra xlsx-specific metadata that calamine's `Data`/`Range` model doesn't
+//! carry: per-cell number formats, column widths, hyperlinks. These are read
+//! directly from the package's XML parts since calamine only exposes
+//! evaluated cell values.
+//!
+//! [`XlsxMeta::open`] parses the workbook-level parts (the sheet name/part
+//! map and the shared style table) once; the per-sheet lookups below reuse
+//! that instead of re-opening the zip archive and re-parsing `styles.xml`
+//! for every sheet.
+
+use anyhow::{Context, Result};
+use quick_xml::events::Event;
+use quick_xml::Reader as XmlReader;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use zip::ZipArchive;
+
+/// How a preserved number format should be treated by the writer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormatKind {
+    DateOnly,
+    TimeOnly,
+    DateTime,
+    Other,
+}
+
+/// A number format carried over from the source cell's style.
+#[derive(Debug, Clone)]
+pub struct CellFormat {
+    pub code: String,
+    pub kind: FormatKind,
+}
+
+/// Number formats for a sheet, keyed by `(row, col)` relative to the range
+/// they were read for (0-indexed, matching calamine's `Range::rows()`).
+pub type SheetFormats = HashMap<(usize, usize), CellFormat>;
+
+/// A hyperlink read from a source cell.
+#[derive(Debug, Clone)]
+pub struct CellHyperlink {
+    pub url: String,
+}
+
+const BUILTIN_DATE_IDS: &[u32] = &[14, 15, 16, 17, 27, 30, 36, 50, 57];
+const BUILTIN_TIME_IDS: &[u32] = &[18, 19, 20, 21, 45, 46, 47];
+const BUILTIN_DATETIME_IDS: &[u32] = &[22];
+
+const FALLBACK_DATE_FORMAT: &str = "yyyy-mm-dd";
+const FALLBACK_TIME_FORMAT: &str = "hh:mm:ss";
+const FALLBACK_DATETIME_FORMAT: &str = "yyyy-mm-dd hh:mm:ss";
+
+fn builtin_format_code(id: u32) -> Option<&'static str> {
+    Some(match id {
+        9 => "0%",
+        10 => "0.00%",
+        14 => "m/d/yyyy",
+        18 => "h:mm AM/PM",
+        19 => "h:mm:ss AM/PM",
+        20 => "h:mm",
+        21 => "h:mm:ss",
+        22 => "m/d/yyyy h:mm",
+        _ => return None,
+    })
+}
+
+/// Classify a numFmtId that has no explicit format code available, using
+/// the small set of date/time builtin ranges defined by the xlsx spec.
+fn classify_builtin_id(id: u32) -> FormatKind {
+    if BUILTIN_DATETIME_IDS.contains(&id) {
+        FormatKind::DateTime
+    } else if BUILTIN_DATE_IDS.contains(&id) {
+        FormatKind::DateOnly
+    } else if BUILTIN_TIME_IDS.contains(&id) {
+        FormatKind::TimeOnly
+    } else {
+        FormatKind::Other
+    }
+}
+
+/// Classify a format code by its date/time component letters.
+fn classify_code(code: &str) -> FormatKind {
+    let lower = code.to_ascii_lowercase();
+    let has_date = lower.contains('y') || lower.contains('d');
+    let has_time = lower.contains('h') || lower.contains('s');
+    match (has_date, has_time) {
+        (true, true) => FormatKind::DateTime,
+        (true, false) => FormatKind::DateOnly,
+        (false, true) => FormatKind::TimeOnly,
+        (false, false) => FormatKind::Other,
+    }
+}
+
+/// Resolve a numFmtId to the format to preserve: the workbook's own custom
+/// code, one of the handful of builtin codes we know verbatim, or — for a
+/// builtin id we recognize as date/time/datetime but don't have the exact
+/// code for — a generic fallback pattern for that shape, so the cell still
+/// round-trips as roughly the right kind of value instead of losing its
+/// format entirely.
+fn resolve_format(id: u32, custom_formats: &HashMap<u32, String>) -> Option<CellFormat> {
+    if let Some(code) = custom_formats.get(&id) {
+        return Some(CellFormat {
+            code: code.clone(),
+            kind: classify_code(code),
+        });
+    }
+    if let Some(code) = builtin_format_code(id) {
+        return Some(CellFormat {
+            code: code.to_string(),
+            kind: classify_code(code),
+        });
+    }
+    let kind = classify_builtin_id(id);
+    let fallback = match kind {
+        FormatKind::DateOnly => FALLBACK_DATE_FORMAT,
+        FormatKind::TimeOnly => FALLBACK_TIME_FORMAT,
+        FormatKind::DateTime => FALLBACK_DATETIME_FORMAT,
+        FormatKind::Other => return None,
+    };
+    Some(CellFormat {
+        code: fallback.to_string(),
+        kind,
+    })
+}
+
+/// Workbook-level xlsx metadata, parsed once and reused across sheets.
+pub struct XlsxMeta {
+    archive: ZipArchive<File>,
+    sheet_parts: HashMap<String, String>,
+    custom_formats: HashMap<u32, String>,
+    cell_xf_format_ids: Vec<u32>,
+}
+
+impl XlsxMeta {
+    /// Open `path` and parse its sheet map and shared style table once.
+    /// Returns `None` for non-xlsx workbooks or if the sheet map can't be
+    /// read; a missing `styles.xml` only leaves the style table empty.
+    pub fn open(path: &Path) -> Result<Option<Self>> {
+        let Some(mut archive) = open_xlsx_zip(path)? else {
+            return Ok(None);
+        };
+        let Some(sheet_parts) = read_sheet_parts(&mut archive)? else {
+            return Ok(None);
+        };
+        let (custom_formats, cell_xf_format_ids) = read_styles(&mut archive);
+
+        Ok(Some(Self {
+            archive,
+            sheet_parts,
+            custom_formats,
+            cell_xf_format_ids,
+        }))
+    }
+
+    /// Number formats for every formatted cell in `sheet_name`, keyed
+    /// relative to `range_start` (calamine's `Range::start()` for the same
+    /// sheet) so callers can probe the map with the same indices they get
+    /// from `Range::rows().enumerate()`, rather than the absolute sheet
+    /// position the `r="B3"` XML refs are written in.
+    pub fn sheet_formats(&mut self, sheet_name: &str, range_start: (usize, usize)) -> Result<SheetFormats> {
+        let Some(sheet_xml) = self.read_sheet_xml(sheet_name)? else {
+            return Ok(SheetFormats::new());
+        };
+
+        let mut formats = SheetFormats::new();
+        let mut reader = XmlReader::from_str(&sheet_xml);
+        reader.config_mut().trim_text(true);
+        let mut buf = Vec::new();
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Ok(Event::Empty(e)) | Ok(Event::Start(e)) if e.name().as_ref() == b"c" => {
+                    let mut cell_ref = None;
+                    let mut style_idx = None;
+                    for attr in e.attributes().flatten() {
+                        match attr.key.as_ref() {
+                            b"r" => {
+                                cell_ref = Some(String::from_utf8_lossy(&attr.value).into_owned())
+                            }
+                            b"s" => {
+                                style_idx = String::from_utf8_lossy(&attr.value).parse::<u32>().ok()
+                            }
+                            _ => {}
+                        }
+                    }
+                    if let (Some(cell_ref), Some(style_idx)) = (cell_ref, style_idx) {
+                        if let Some(coord) = parse_cell_ref(&cell_ref).and_then(|c| relative_to(c, range_start)) {
+                            if let Some(&num_fmt_id) =
+                                self.cell_xf_format_ids.get(style_idx as usize)
+                            {
+                                if let Some(format) = resolve_format(num_fmt_id, &self.custom_formats) {
+                                    formats.insert(coord, format);
+                                }
+                            }
+                        }
+                    }
+                }
+                Ok(Event::Eof) => break,
+                Ok(_) => {}
+                Err(_) => break,
+            }
+            buf.clear();
+        }
+
+        Ok(formats)
+    }
+
+    /// The external hyperlinks attached to cells in `sheet_name`, keyed
+    /// relative to `range_start` the same way [`XlsxMeta::sheet_formats`]
+    /// is. Internal (same-workbook) links have no relationship target and
+    /// are skipped.
+    pub fn sheet_hyperlinks(
+        &mut self,
+        sheet_name: &str,
+        range_start: (usize, usize),
+    ) -> Result<HashMap<(usize, usize), CellHyperlink>> {
+        let Some(sheet_part) = self.sheet_parts.get(sheet_name).cloned() else {
+            return Ok(HashMap::new());
+        };
+        let Some(sheet_xml) = self.read_sheet_xml(sheet_name)? else {
+            return Ok(HashMap::new());
+        };
+
+        let rels_targets = read_zip_entry_string(&mut self.archive, &sheet_rels_part_path(&sheet_part))
+            .ok()
+            .map(|xml| parse_relationships(&xml))
+            .unwrap_or_default();
+
+        let mut links = HashMap::new();
+        let mut reader = XmlReader::from_str(&sheet_xml);
+        reader.config_mut().trim_text(true);
+        let mut buf = Vec::new();
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Ok(Event::Empty(e)) | Ok(Event::Start(e)) if e.name().as_ref() == b"hyperlink" => {
+                    let mut cell_ref = None;
+                    let mut rel_id = None;
+                    for attr in e.attributes().flatten() {
+                        match attr.key.as_ref() {
+                            b"ref" => {
+                                cell_ref = Some(String::from_utf8_lossy(&attr.value).into_owned())
+                            }
+                            b"r:id" => {
+                                rel_id = Some(String::from_utf8_lossy(&attr.value).into_owned())
+                            }
+                            _ => {}
+                        }
+                    }
+                    if let (Some(cell_ref), Some(rel_id)) = (cell_ref, rel_id) {
+                        // `ref` is a single cell for a plain link, but a
+                        // range (e.g. "B2:C4") when the link sits over a
+                        // merged cell. Anchor to the range's first cell.
+                        let anchor = cell_ref.split(':').next().unwrap_or(&cell_ref);
+                        if let Some(coord) = parse_cell_ref(anchor).and_then(|c| relative_to(c, range_start)) {
+                            if let Some(url) = rels_targets.get(&rel_id) {
+                                links.insert(coord, CellHyperlink { url: url.clone() });
+                            }
+                        }
+                    }
+                }
+                Ok(Event::Eof) => break,
+                Ok(_) => {}
+                Err(_) => break,
+            }
+            buf.clear();
+        }
+
+        Ok(links)
+    }
+
+    /// Each column's declared width (in Excel's character-width units) for
+    /// `sheet_name`, indexed by column number relative to `range_start_col`
+    /// (calamine's `Range::start()` column for the same sheet). Columns
+    /// with no explicit `<col>` entry are omitted.
+    pub fn column_widths(&mut self, sheet_name: &str, range_start_col: usize) -> Result<HashMap<usize, f64>> {
+        let Some(sheet_xml) = self.read_sheet_xml(sheet_name)? else {
+            return Ok(HashMap::new());
+        };
+
+        let mut widths = HashMap::new();
+        let mut reader = XmlReader::from_str(&sheet_xml);
+        reader.config_mut().trim_text(true);
+        let mut buf = Vec::new();
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Ok(Event::Empty(e)) | Ok(Event::Start(e)) if e.name().as_ref() == b"col" => {
+                    let mut min = None;
+                    let mut max = None;
+                    let mut width = None;
+                    for attr in e.attributes().flatten() {
+                        match attr.key.as_ref() {
+                            b"min" => min = String::from_utf8_lossy(&attr.value).parse::<usize>().ok(),
+                            b"max" => max = String::from_utf8_lossy(&attr.value).parse::<usize>().ok(),
+                            b"width" => {
+                                width = String::from_utf8_lossy(&attr.value).parse::<f64>().ok()
+                            }
+                            _ => {}
+                        }
+                    }
+                    if let (Some(min), Some(max), Some(width)) = (min, max, width) {
+                        for col in min..=max {
+                            if let Some(col_idx) =
+                                col.checked_sub(1).and_then(|c| c.checked_sub(range_start_col))
+                            {
+                                widths.insert(col_idx, width);
+                            }
+                        }
+                    }
+                }
+                Ok(Event::Eof) => break,
+                Ok(_) => {}
+                Err(_) => break,
+            }
+            buf.clear();
+        }
+
+        Ok(widths)
+    }
+
+    fn read_sheet_xml(&mut self, sheet_name: &str) -> Result<Option<String>> {
+        let Some(sheet_part) = self.sheet_parts.get(sheet_name).cloned() else {
+            return Ok(None);
+        };
+        Ok(Some(read_zip_entry_string(&mut self.archive, &sheet_part)?))
+    }
+}
+
+/// Open `path` as an xlsx zip archive, returning `None` if it isn't one.
+fn open_xlsx_zip(path: &Path) -> Result<Option<ZipArchive<File>>> {
+    if path.extension().and_then(|e| e.to_str()) != Some("xlsx") {
+        return Ok(None);
+    }
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    match ZipArchive::new(file) {
+        Ok(archive) => Ok(Some(archive)),
+        Err(_) => Ok(None),
+    }
+}
+
+fn read_zip_entry_string(archive: &mut ZipArchive<File>, name: &str) -> Result<String> {
+    let mut entry = archive
+        .by_name(name)
+        .with_context(|| format!("Missing zip entry: {name}"))?;
+    let mut contents = String::new();
+    entry.read_to_string(&mut contents)?;
+    Ok(contents)
+}
+
+/// Map every sheet's display name to its `xl/worksheets/sheetN.xml` part,
+/// in a single pass over `workbook.xml` and its rels.
+fn read_sheet_parts(archive: &mut ZipArchive<File>) -> Result<Option<HashMap<String, String>>> {
+    let workbook_xml = match read_zip_entry_string(archive, "xl/workbook.xml") {
+        Ok(s) => s,
+        Err(_) => return Ok(None),
+    };
+    let rels_xml = match read_zip_entry_string(archive, "xl/_rels/workbook.xml.rels") {
+        Ok(s) => s,
+        Err(_) => return Ok(None),
+    };
+    let rel_targets = parse_relationships(&rels_xml);
+
+    let mut sheet_rel_ids = Vec::new();
+    let mut reader = XmlReader::from_str(&workbook_xml);
+    reader.config_mut().trim_text(true);
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Empty(e)) | Ok(Event::Start(e)) if e.name().as_ref() == b"sheet" => {
+                let mut name = None;
+                let mut rid = None;
+                for attr in e.attributes().flatten() {
+                    match attr.key.as_ref() {
+                        b"name" => name = Some(String::from_utf8_lossy(&attr.value).into_owned()),
+                        b"r:id" => rid = Some(String::from_utf8_lossy(&attr.value).into_owned()),
+                        _ => {}
+                    }
+                }
+                if let (Some(name), Some(rid)) = (name, rid) {
+                    sheet_rel_ids.push((name, rid));
+                }
+            }
+            Ok(Event::Eof) => break,
+            Ok(_) => {}
+            Err(_) => break,
+        }
+        buf.clear();
+    }
+
+    let sheet_parts = sheet_rel_ids
+        .into_iter()
+        .filter_map(|(name, rid)| rel_targets.get(&rid).map(|t| (name, format!("xl/{t}"))))
+        .collect();
+    Ok(Some(sheet_parts))
+}
+
+/// Parse a `.rels` part into a map from relationship `Id` to `Target`.
+fn parse_relationships(rels_xml: &str) -> HashMap<String, String> {
+    let mut targets = HashMap::new();
+    let mut reader = XmlReader::from_str(rels_xml);
+    reader.config_mut().trim_text(true);
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Empty(e)) | Ok(Event::Start(e)) if e.name().as_ref() == b"Relationship" => {
+                let mut id = None;
+                let mut href = None;
+                for attr in e.attributes().flatten() {
+                    match attr.key.as_ref() {
+                        b"Id" => id = Some(String::from_utf8_lossy(&attr.value).into_owned()),
+                        b"Target" => {
+                            href = Some(String::from_utf8_lossy(&attr.value).into_owned())
+                        }
+                        _ => {}
+                    }
+                }
+                if let (Some(id), Some(href)) = (id, href) {
+                    targets.insert(id, href);
+                }
+            }
+            Ok(Event::Eof) => break,
+            Ok(_) => {}
+            Err(_) => break,
+        }
+        buf.clear();
+    }
+    targets
+}
+
+/// Read `xl/styles.xml` once, returning both the custom `numFmtId ->
+/// format code` table and the `numFmtId` referenced by each `cellXfs`
+/// entry (indexed by style index, the `s` attribute on a `<c>` element).
+/// Returns empty collections if `styles.xml` is missing.
+fn read_styles(archive: &mut ZipArchive<File>) -> (HashMap<u32, String>, Vec<u32>) {
+    let styles_xml = match read_zip_entry_string(archive, "xl/styles.xml") {
+        Ok(s) => s,
+        Err(_) => return (HashMap::new(), Vec::new()),
+    };
+
+    let mut custom_formats = HashMap::new();
+    let mut cell_xf_format_ids = Vec::new();
+    let mut in_cell_xfs = false;
+
+    let mut reader = XmlReader::from_str(&styles_xml);
+    reader.config_mut().trim_text(true);
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) if e.name().as_ref() == b"cellXfs" => in_cell_xfs = true,
+            Ok(Event::End(e)) if e.name().as_ref() == b"cellXfs" => in_cell_xfs = false,
+            Ok(Event::Empty(e)) | Ok(Event::Start(e)) if e.name().as_ref() == b"numFmt" => {
+                let mut id = None;
+                let mut code = None;
+                for attr in e.attributes().flatten() {
+                    match attr.key.as_ref() {
+                        b"numFmtId" => id = String::from_utf8_lossy(&attr.value).parse().ok(),
+                        b"formatCode" => {
+                            code = Some(String::from_utf8_lossy(&attr.value).into_owned())
+                        }
+                        _ => {}
+                    }
+                }
+                if let (Some(id), Some(code)) = (id, code) {
+                    custom_formats.insert(id, code);
+                }
+            }
+            Ok(Event::Empty(e)) | Ok(Event::Start(e))
+                if in_cell_xfs && e.name().as_ref() == b"xf" =>
+            {
+                let num_fmt_id = e
+                    .attributes()
+                    .flatten()
+                    .find(|a| a.key.as_ref() == b"numFmtId")
+                    .and_then(|a| String::from_utf8_lossy(&a.value).parse().ok())
+                    .unwrap_or(0);
+                cell_xf_format_ids.push(num_fmt_id);
+            }
+            Ok(Event::Eof) => break,
+            Ok(_) => {}
+            Err(_) => break,
+        }
+        buf.clear();
+    }
+
+    (custom_formats, cell_xf_format_ids)
+}
+
+/// The `_rels` sibling part of a zip entry, e.g.
+/// `xl/worksheets/sheet1.xml` -> `xl/worksheets/_rels/sheet1.xml.rels`.
+fn sheet_rels_part_path(sheet_part: &str) -> String {
+    match sheet_part.rsplit_once('/') {
+        Some((dir, file)) => format!("{dir}/_rels/{file}.rels"),
+        None => format!("_rels/{sheet_part}.rels"),
+    }
+}
+
+/// Offset an absolute `(row, col)` by a range's start, as returned by
+/// calamine's `Range::start()`. Returns `None` for a position outside the
+/// range (shouldn't normally happen for cells actually inside the sheet's
+/// used range, but guards against underflow instead of panicking).
+fn relative_to(coord: (usize, usize), range_start: (usize, usize)) -> Option<(usize, usize)> {
+    let row = coord.0.checked_sub(range_start.0)?;
+    let col = coord.1.checked_sub(range_start.1)?;
+    Some((row, col))
+}
+
+/// Parse an `A1`-style cell reference into 0-indexed `(row, col)`.
+fn parse_cell_ref(cell_ref: &str) -> Option<(usize, usize)> {
+    let split_at = cell_ref.find(|c: char| c.is_ascii_digit())?;
+    let (col_letters, row_digits) = cell_ref.split_at(split_at);
+    if col_letters.is_empty() || row_digits.is_empty() {
+        return None;
+    }
+
+    let mut col = 0usize;
+    for c in col_letters.chars() {
+        if !c.is_ascii_alphabetic() {
+            return None;
+        }
+        col = col * 26 + (c.to_ascii_uppercase() as usize - 'A' as usize + 1);
+    }
+    let row: usize = row_digits.parse().ok()?;
+
+    Some((row.checked_sub(1)?, col.checked_sub(1)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_simple_and_multi_letter_cell_refs() {
+        assert_eq!(parse_cell_ref("A1"), Some((0, 0)));
+        assert_eq!(parse_cell_ref("C5"), Some((4, 2)));
+        assert_eq!(parse_cell_ref("AA10"), Some((9, 26)));
+        assert_eq!(parse_cell_ref("AZ1"), Some((0, 51)));
+    }
+
+    #[test]
+    fn rejects_malformed_cell_refs() {
+        assert_eq!(parse_cell_ref(""), None);
+        assert_eq!(parse_cell_ref("123"), None);
+        assert_eq!(parse_cell_ref("A"), None);
+        assert_eq!(parse_cell_ref("A0"), None);
+        assert_eq!(parse_cell_ref("1A"), None);
+    }
+
+    #[test]
+    fn anchors_range_refs_to_their_first_cell() {
+        let anchor = "B2:C4".split(':').next().unwrap();
+        assert_eq!(parse_cell_ref(anchor), Some((1, 1)));
+    }
+
+    #[test]
+    fn relative_to_offsets_by_the_ranges_start() {
+        assert_eq!(relative_to((5, 3), (2, 1)), Some((3, 2)));
+        assert_eq!(relative_to((0, 0), (0, 0)), Some((0, 0)));
+        assert_eq!(relative_to((1, 1), (2, 2)), None);
+    }
+
+    #[test]
+    fn classifies_format_codes_by_component_letters() {
+        assert_eq!(classify_code("m/d/yyyy"), FormatKind::DateOnly);
+        assert_eq!(classify_code("h:mm:ss"), FormatKind::TimeOnly);
+        assert_eq!(classify_code("m/d/yyyy h:mm"), FormatKind::DateTime);
+        assert_eq!(classify_code("0.00%"), FormatKind::Other);
+    }
+
+    #[test]
+    fn resolves_known_builtin_ids_verbatim() {
+        let custom = HashMap::new();
+        let format = resolve_format(14, &custom).unwrap();
+        assert_eq!(format.code, "m/d/yyyy");
+        assert_eq!(format.kind, FormatKind::DateOnly);
+    }
+
+    #[test]
+    fn falls_back_to_a_generic_pattern_for_unmapped_builtin_date_ids() {
+        // 17 is a BUILTIN_DATE_IDS entry with no exact code in builtin_format_code.
+        let custom = HashMap::new();
+        let format = resolve_format(17, &custom).unwrap();
+        assert_eq!(format.kind, FormatKind::DateOnly);
+        assert_eq!(format.code, FALLBACK_DATE_FORMAT);
+    }
+
+    #[test]
+    fn unknown_non_date_ids_resolve_to_nothing() {
+        let custom = HashMap::new();
+        assert!(resolve_format(164, &custom).is_none());
+    }
+
+    #[test]
+    fn prefers_custom_format_over_builtin() {
+        let mut custom = HashMap::new();
+        custom.insert(14, "dd-mm-yyyy".to_string());
+        let format = resolve_format(14, &custom).unwrap();
+        assert_eq!(format.code, "dd-mm-yyyy");
+    }
+}